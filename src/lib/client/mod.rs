@@ -0,0 +1,37 @@
+pub mod rate_limited;
+
+pub use rate_limited::RateLimited;
+
+use crate::models::GenericPost;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum Error {
+    Request(String),
+    /// The server answered 429; callers should wait `retry_after` before trying again.
+    RateLimited { retry_after: Duration },
+}
+
+impl std::error::Error for Error {}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "client::Error: {:?}", self)
+    }
+}
+
+/// Posts messages and reactions on behalf of the bot.
+pub trait Sender {
+    fn reply(&self, post: &GenericPost, text: &str) -> Result<(), Error>;
+    fn reaction(&self, post: &GenericPost, emoji: &str) -> Result<(), Error>;
+    fn send(&self, channel_id: &str, text: &str) -> Result<(), Error>;
+    fn post(&self, channel_id: &str, text: &str) -> Result<GenericPost, Error>;
+    fn list_reactions(&self, message_id: &str) -> Result<Vec<String>, Error>;
+}
+
+/// Operational messages about the bot itself, as opposed to chat content.
+pub trait Notifier {
+    fn debug(&self, message: &str) -> Result<(), Error>;
+    fn startup(&self, message: &str) -> Result<(), Error>;
+    fn notify(&self, message: &str) -> Result<(), Error>;
+}
@@ -0,0 +1,247 @@
+use crate::client::{Error, Notifier, Sender};
+use crate::models::GenericPost;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Mattermost rate-limits per endpoint, not globally, so each distinct call a
+/// `Sender`/`Notifier` exposes gets its own bucket.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+enum Route {
+    Reply,
+    Reaction,
+    Send,
+    Post,
+    ListReactions,
+    Debug,
+    Startup,
+    Notify,
+}
+
+const DEFAULT_LIMIT: u32 = 60;
+const DEFAULT_WINDOW: Duration = Duration::from_secs(60);
+
+struct Bucket {
+    limit: u32,
+    remaining: u32,
+    reset_at: Instant,
+}
+
+impl Bucket {
+    fn new() -> Self {
+        Self {
+            limit: DEFAULT_LIMIT,
+            remaining: DEFAULT_LIMIT,
+            reset_at: Instant::now() + DEFAULT_WINDOW,
+        }
+    }
+}
+
+fn route_named(name: &str) -> Option<Route> {
+    Some(match name {
+        "reply" => Route::Reply,
+        "reaction" => Route::Reaction,
+        "send" => Route::Send,
+        "post" => Route::Post,
+        "list_reactions" => Route::ListReactions,
+        "debug" => Route::Debug,
+        "startup" => Route::Startup,
+        "notify" => Route::Notify,
+        _ => return None,
+    })
+}
+
+/// Recalibrate `route`'s bucket in `buckets` from the `X-RateLimit-*` headers
+/// of a response. Shared by [`RateLimited`] and [`RateLimitObserver`], which
+/// both hold a handle to the same bucket map.
+fn observe(buckets: &Mutex<HashMap<Route, Bucket>>, route_name: &str, limit: u32, remaining: u32, reset_at: Instant) {
+    let route = match route_named(route_name) {
+        Some(route) => route,
+        None => return,
+    };
+    buckets.lock().unwrap().insert(
+        route,
+        Bucket {
+            limit,
+            remaining,
+            reset_at,
+        },
+    );
+}
+
+/// A cheap, cloneable handle onto a [`RateLimited`] wrapper's bucket map,
+/// independent of the wrapper and the `C` it wraps. A real HTTP-backed
+/// `Sender`/`Notifier` implementation holds one of these (see
+/// [`RateLimited::observer`]) and calls [`RateLimitObserver::observe`] after
+/// reading a response's `X-RateLimit-*` headers, so the bucket reflects what
+/// Mattermost actually told us rather than our own guessed default.
+#[derive(Clone)]
+pub struct RateLimitObserver {
+    buckets: Arc<Mutex<HashMap<Route, Bucket>>>,
+}
+
+impl RateLimitObserver {
+    pub fn observe(&self, route_name: &str, limit: u32, remaining: u32, reset_at: Instant) {
+        observe(&self.buckets, route_name, limit, remaining, reset_at);
+    }
+}
+
+/// Wraps a real `Sender`/`Notifier` with a token bucket per route, recalibrated
+/// from `X-RateLimit-*` response headers via [`RateLimited::observer`], and
+/// transparently sleeps-and-retries once on a 429 instead of surfacing it to
+/// the handler that triggered the call.
+pub struct RateLimited<C> {
+    inner: C,
+    buckets: Arc<Mutex<HashMap<Route, Bucket>>>,
+}
+
+impl<C> RateLimited<C> {
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// A handle the inner client can hold onto and call `observe()` on, without
+    /// needing a reference back to this wrapper.
+    pub fn observer(&self) -> RateLimitObserver {
+        RateLimitObserver {
+            buckets: Arc::clone(&self.buckets),
+        }
+    }
+
+    /// Block until a token is available for `route`, then consume it.
+    fn acquire(&self, route: Route) {
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().unwrap();
+                let bucket = buckets.entry(route).or_insert_with(Bucket::new);
+                let now = Instant::now();
+                if now >= bucket.reset_at {
+                    bucket.remaining = bucket.limit;
+                    bucket.reset_at = now + DEFAULT_WINDOW;
+                }
+
+                if bucket.remaining > 0 {
+                    bucket.remaining -= 1;
+                    None
+                } else {
+                    Some(bucket.reset_at.saturating_duration_since(now))
+                }
+            };
+
+            match wait {
+                None => return,
+                Some(duration) => thread::sleep(duration),
+            }
+        }
+    }
+
+    /// Acquire a token for `route`, run `f`, and if the server came back with a
+    /// 429 sleep for `Retry-After` and try exactly once more.
+    fn call<T>(&self, route: Route, f: impl Fn() -> Result<T, Error>) -> Result<T, Error> {
+        self.acquire(route);
+        match f() {
+            Err(Error::RateLimited { retry_after }) => {
+                thread::sleep(retry_after);
+                f()
+            }
+            other => other,
+        }
+    }
+}
+
+impl<C: Sender> Sender for RateLimited<C> {
+    fn reply(&self, post: &GenericPost, text: &str) -> Result<(), Error> {
+        self.call(Route::Reply, || self.inner.reply(post, text))
+    }
+
+    fn reaction(&self, post: &GenericPost, emoji: &str) -> Result<(), Error> {
+        self.call(Route::Reaction, || self.inner.reaction(post, emoji))
+    }
+
+    fn send(&self, channel_id: &str, text: &str) -> Result<(), Error> {
+        self.call(Route::Send, || self.inner.send(channel_id, text))
+    }
+
+    fn post(&self, channel_id: &str, text: &str) -> Result<GenericPost, Error> {
+        self.call(Route::Post, || self.inner.post(channel_id, text))
+    }
+
+    fn list_reactions(&self, message_id: &str) -> Result<Vec<String>, Error> {
+        self.call(Route::ListReactions, || self.inner.list_reactions(message_id))
+    }
+}
+
+impl<C: Notifier> Notifier for RateLimited<C> {
+    fn debug(&self, message: &str) -> Result<(), Error> {
+        self.call(Route::Debug, || self.inner.debug(message))
+    }
+
+    fn startup(&self, message: &str) -> Result<(), Error> {
+        self.call(Route::Startup, || self.inner.startup(message))
+    }
+
+    fn notify(&self, message: &str) -> Result<(), Error> {
+        self.call(Route::Notify, || self.inner.notify(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn observer_recalibrates_the_bucket_the_route_shares_with_the_wrapper() {
+        let rl = RateLimited::new(());
+        let reset_at = Instant::now() + Duration::from_secs(30);
+        rl.observer().observe("reply", 5, 0, reset_at);
+
+        let buckets = rl.buckets.lock().unwrap();
+        let bucket = buckets.get(&Route::Reply).expect("bucket recorded");
+        assert_eq!(bucket.limit, 5);
+        assert_eq!(bucket.remaining, 0);
+        assert_eq!(bucket.reset_at, reset_at);
+    }
+
+    #[test]
+    fn observe_ignores_an_unknown_route_name() {
+        let rl = RateLimited::new(());
+        rl.observer().observe("not_a_route", 5, 0, Instant::now());
+        assert!(rl.buckets.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn call_sleeps_and_retries_exactly_once_on_a_429() {
+        let rl = RateLimited::new(());
+        let attempts = Cell::new(0u32);
+
+        let result = rl.call(Route::Reply, || {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() == 1 {
+                Err(Error::RateLimited {
+                    retry_after: Duration::from_millis(1),
+                })
+            } else {
+                Ok(())
+            }
+        });
+
+        assert!(result.is_ok());
+        assert_eq!(attempts.get(), 2);
+    }
+
+    #[test]
+    fn acquire_blocks_once_the_observed_bucket_is_exhausted() {
+        let rl = RateLimited::new(());
+        let reset_at = Instant::now() + Duration::from_millis(20);
+        rl.observer().observe("send", 1, 0, reset_at);
+
+        let started = Instant::now();
+        rl.acquire(Route::Send);
+        assert!(started.elapsed() >= Duration::from_millis(15));
+    }
+}
@@ -6,6 +6,8 @@ pub struct Conf {
     pub token: String,
     pub threads: u64,
     pub db_url: String,
+    pub state_path: String,
+    pub event_buffer: usize,
 }
 
 impl Conf {
@@ -16,6 +18,11 @@ impl Conf {
             ws_url: std::env::var("BOT_WS_URL").expect("BOT_WS_URL"),
             token: std::env::var("BOT_TOKEN").expect("BOT_TOKEN"),
             db_url: std::env::var("BOT_DB_URL").expect("BOT_DB_URL"),
+            state_path: std::env::var("BOT_STATE_PATH").unwrap_or_else(|_| "flobot_state.cbor".to_string()),
+            event_buffer: std::env::var("BOT_EVENT_BUFFER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(256),
             /*threads: std::env::var("BOT_THREADS")
             .unwrap_or(String::from("1"))
             .parse()
@@ -0,0 +1,96 @@
+pub mod schema;
+pub mod sqlite;
+pub mod tempo;
+
+use diesel::Connection;
+use diesel_migrations::embed_migrations;
+use std::convert::From;
+
+#[cfg(feature = "sqlite")]
+pub type DatabaseConnection = diesel::SqliteConnection;
+
+#[derive(Debug)]
+pub enum Error {
+    Database(String),
+    Migration(String),
+}
+
+impl std::error::Error for Error {}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Migration(e) => write!(f, "Cannot run migrations: {}", e),
+            Error::Database(e) => write!(f, "db::Error: {}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for Error {
+    fn from(e: diesel::result::Error) -> Self {
+        Error::Database(e.to_string())
+    }
+}
+
+impl From<diesel_migrations::RunMigrationsError> for Error {
+    fn from(e: diesel_migrations::RunMigrationsError) -> Self {
+        Error::Migration(e.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+pub trait Trigger {
+    fn list(&self, team_id: &str) -> Result<Vec<crate::models::Trigger>>;
+    fn search(&self, team_id: &str) -> Result<Vec<crate::models::Trigger>>;
+    fn add_text(&self, team_id: &str, trigger: &str, text: &str) -> Result<()>;
+    fn add_emoji(&self, team_id: &str, trigger: &str, emoji: &str) -> Result<()>;
+    fn del(&self, team_id: &str, trigger: &str) -> Result<()>;
+}
+
+/// Reminders are persisted so they survive a restart, unlike `Tempo` which is
+/// purely in-memory. A scheduler thread polls `due` and deletes what it delivers.
+pub trait Reminders {
+    fn add(
+        &self,
+        team_id: &str,
+        channel_id: &str,
+        user_id: &str,
+        remind_at: chrono::NaiveDateTime,
+        message: &str,
+    ) -> Result<()>;
+    fn list(&self, team_id: &str, user_id: &str) -> Result<Vec<crate::models::Reminder>>;
+    fn due(&self, now: chrono::NaiveDateTime) -> Result<Vec<crate::models::Reminder>>;
+    fn del(&self, id: i32) -> Result<()>;
+}
+
+/// Open `!match` RSVPs, persisted so a pending one survives a restart. The
+/// reminder scheduler thread polls `open` to ping participants once
+/// `start_at` arrives or a quorum has joined, then deletes the row.
+pub trait Matches {
+    fn create(
+        &self,
+        message_id: &str,
+        team_id: &str,
+        channel_id: &str,
+        activity: &str,
+        start_at: chrono::NaiveDateTime,
+        bot_user_id: &str,
+    ) -> Result<i32>;
+    fn join(&self, match_id: i32, user_id: &str) -> Result<()>;
+    fn participants(&self, match_id: i32) -> Result<Vec<String>>;
+    /// Every match that hasn't fired yet, regardless of `start_at`.
+    fn open(&self) -> Result<Vec<crate::models::Match>>;
+    fn due(&self, now: chrono::NaiveDateTime) -> Result<Vec<crate::models::Match>>;
+    fn del(&self, match_id: i32) -> Result<()>;
+}
+
+embed_migrations!("migrations");
+
+pub fn conn(db_url: &str) -> DatabaseConnection {
+    DatabaseConnection::establish(db_url).expect("db connection")
+}
+
+pub fn run_migrations(conn: &DatabaseConnection) -> Result<()> {
+    Ok(embedded_migrations::run(conn)?)
+}
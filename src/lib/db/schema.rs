@@ -0,0 +1,29 @@
+table! {
+    reminders (id) {
+        id -> Integer,
+        team_id -> Text,
+        channel_id -> Text,
+        user_id -> Text,
+        remind_at -> Timestamp,
+        message -> Text,
+    }
+}
+
+table! {
+    matches (id) {
+        id -> Integer,
+        message_id -> Text,
+        team_id -> Text,
+        channel_id -> Text,
+        activity -> Text,
+        start_at -> Timestamp,
+        bot_user_id -> Text,
+    }
+}
+
+table! {
+    match_participants (match_id, user_id) {
+        match_id -> Integer,
+        user_id -> Text,
+    }
+}
@@ -0,0 +1,71 @@
+use crate::db::schema::match_participants::dsl as participants_table;
+use crate::db::schema::matches::dsl as table;
+use crate::db::Result;
+use crate::models::Match;
+use diesel::prelude::*;
+use diesel::sql_types::Integer;
+
+fn last_insert_rowid(conn: &diesel::SqliteConnection) -> diesel::QueryResult<i32> {
+    diesel::select(diesel::dsl::sql::<Integer>("last_insert_rowid()")).get_result(conn)
+}
+
+impl crate::db::Matches for super::Sqlite {
+    fn create(
+        &self,
+        message_id: &str,
+        team_id: &str,
+        channel_id: &str,
+        activity: &str,
+        start_at: chrono::NaiveDateTime,
+        bot_user_id: &str,
+    ) -> Result<i32> {
+        let conn = self.db.lock().unwrap();
+        let _ = diesel::insert_into(table::matches)
+            .values((
+                table::message_id.eq(message_id),
+                table::team_id.eq(team_id),
+                table::channel_id.eq(channel_id),
+                table::activity.eq(activity),
+                table::start_at.eq(start_at),
+                table::bot_user_id.eq(bot_user_id),
+            ))
+            .execute(&*conn)?;
+        Ok(last_insert_rowid(&conn)?)
+    }
+
+    fn join(&self, match_id: i32, user_id: &str) -> Result<()> {
+        // a user reacting twice shouldn't error, so ignore the duplicate key.
+        let _ = diesel::insert_or_ignore_into(participants_table::match_participants)
+            .values((
+                participants_table::match_id.eq(match_id),
+                participants_table::user_id.eq(user_id),
+            ))
+            .execute(&*self.db.lock().unwrap())?;
+        Ok(())
+    }
+
+    fn participants(&self, match_id: i32) -> Result<Vec<String>> {
+        Ok(participants_table::match_participants
+            .filter(participants_table::match_id.eq(match_id))
+            .select(participants_table::user_id)
+            .load::<String>(&*self.db.lock().unwrap())?)
+    }
+
+    fn open(&self) -> Result<Vec<Match>> {
+        Ok(table::matches.load::<Match>(&*self.db.lock().unwrap())?)
+    }
+
+    fn due(&self, now: chrono::NaiveDateTime) -> Result<Vec<Match>> {
+        Ok(table::matches
+            .filter(table::start_at.le(now))
+            .load::<Match>(&*self.db.lock().unwrap())?)
+    }
+
+    fn del(&self, match_id: i32) -> Result<()> {
+        let conn = self.db.lock().unwrap();
+        let _ = diesel::delete(participants_table::match_participants.filter(participants_table::match_id.eq(match_id)))
+            .execute(&*conn)?;
+        let _ = diesel::delete(table::matches.filter(table::id.eq(match_id))).execute(&*conn)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+pub mod matches;
+pub mod reminders;
+
+use crate::db::DatabaseConnection;
+use std::sync::{Arc, Mutex};
+
+pub struct Sqlite {
+    db: Arc<Mutex<DatabaseConnection>>,
+}
+
+impl Sqlite {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self {
+            db: Arc::new(Mutex::new(conn)),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::db::schema::reminders::dsl as table;
+use crate::db::Result;
+use crate::models::Reminder;
+use diesel::prelude::*;
+
+impl crate::db::Reminders for super::Sqlite {
+    fn add(
+        &self,
+        team_id: &str,
+        channel_id: &str,
+        user_id: &str,
+        remind_at: chrono::NaiveDateTime,
+        message: &str,
+    ) -> Result<()> {
+        let _ = diesel::insert_into(table::reminders)
+            .values((
+                table::team_id.eq(team_id),
+                table::channel_id.eq(channel_id),
+                table::user_id.eq(user_id),
+                table::remind_at.eq(remind_at),
+                table::message.eq(message),
+            ))
+            .execute(&*self.db.lock().unwrap())?;
+        Ok(())
+    }
+
+    fn list(&self, team_id: &str, user_id: &str) -> Result<Vec<Reminder>> {
+        Ok(table::reminders
+            .filter(table::team_id.eq(team_id).and(table::user_id.eq(user_id)))
+            .order_by(table::remind_at)
+            .load::<Reminder>(&*self.db.lock().unwrap())?)
+    }
+
+    fn due(&self, now: chrono::NaiveDateTime) -> Result<Vec<Reminder>> {
+        Ok(table::reminders
+            .filter(table::remind_at.le(now))
+            .load::<Reminder>(&*self.db.lock().unwrap())?)
+    }
+
+    fn del(&self, id: i32) -> Result<()> {
+        let _ = diesel::delete(table::reminders.filter(table::id.eq(id)))
+            .execute(&*self.db.lock().unwrap())?;
+        Ok(())
+    }
+}
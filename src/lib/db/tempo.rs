@@ -1,20 +1,35 @@
-use std::cmp::Eq;
-use std::collections::HashMap;
+use std::cmp::{Eq, Reverse};
+use std::collections::{BinaryHeap, HashMap};
 use std::hash::Hash;
 use std::ops::Add;
 use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::{Duration, Instant};
 
+struct Store<T> {
+    map: HashMap<T, Instant>,
+    // min-heap on expiry, so the janitor (and capacity eviction) always look at
+    // the soonest-to-expire key first without scanning the whole map.
+    heap: BinaryHeap<Reverse<(Instant, T)>>,
+    max_capacity: Option<usize>,
+}
+
 #[derive(Clone)]
 pub struct Tempo<T> {
-    store: Arc<Mutex<HashMap<T, Instant>>>,
+    store: Arc<Mutex<Store<T>>>,
 }
 
-/// Tempo provides a simple interface to store keys and check for there expiration. No self-cleaning,
-/// meaning it's not suitable for large quantities of data: once a key is added, it will be removed
-/// only on lookup.
+/// Tempo provides a simple interface to store keys and check for their expiration.
+///
+/// Plain `new()` keeps the old lazy behaviour: a key is only ever removed on
+/// lookup, which doesn't scale to large quantities of data. `with_eviction`
+/// spawns a background janitor thread that wakes on an interval and sweeps
+/// expired keys, and `with_capacity` evicts the soonest-to-expire key once a
+/// maximum size is reached, making Tempo usable as a general rate-limiter for
+/// thousands of active keys.
 ///
-/// Internaly, it uses a standard Arc container so it's safe to use with threads.
+/// Internally, it uses a standard Arc<Mutex<...>> container so it's safe to use
+/// and clone across threads.
 ///
 /// # Example
 ///
@@ -23,50 +38,103 @@ pub struct Tempo<T> {
 /// # use flobot::db::tempo::Tempo;
 /// # use std::thread::sleep;
 /// use std::time::Duration;
-/// # let mut tempo = Tempo::new();
-/// assert_eq!(false, tempo.exists("try"));
+/// # let tempo = Tempo::new();
+/// assert_eq!(false, tempo.exists(&"try"));
 ///
 /// tempo.set("try", Duration::from_secs(1));
-/// assert_eq!(true, tempo.exists("try"));
+/// assert_eq!(true, tempo.exists(&"try"));
 ///
 /// tempo.set("expire", Duration::from_millis(100));
-/// assert_eq!(true, tempo.exists("expire"));
+/// assert_eq!(true, tempo.exists(&"expire"));
 ///
 /// sleep(Duration::from_millis(101));
-/// assert_eq!(false, tempo.exists("expire"));
+/// assert_eq!(false, tempo.exists(&"expire"));
 ///
-/// // Share your tempo to other threads.
-/// let mut ctempo = tempo.clone(); // move this one to your new thread
+/// // Share your tempo with other threads.
+/// let ctempo = tempo.clone(); // move this one to your new thread
 /// tempo.set("cloned", Duration::from_secs(1)); // "main" thread sets a key
-/// assert_eq!(true, ctempo.exists("cloned")); // this key is available in the other thread
+/// assert_eq!(true, ctempo.exists(&"cloned")); // this key is available in the other thread
 /// # }
 /// ```
-impl<T: Hash + Eq> Tempo<T> {
+impl<T: Hash + Eq + Ord + Clone + Send + Sync + 'static> Tempo<T> {
     pub fn new() -> Self {
         Self {
-            store: Arc::new(Mutex::new(HashMap::new())),
+            store: Arc::new(Mutex::new(Store {
+                map: HashMap::new(),
+                heap: BinaryHeap::new(),
+                max_capacity: None,
+            })),
         }
     }
 
-    pub fn set(&mut self, key: T, ttl: Duration) {
-        let expire_in = Instant::now().add(ttl);
+    /// Like `new`, but also spawns a background thread waking every `interval`
+    /// that pops expired entries off the heap and removes them from the map,
+    /// guarding against a key that was re-`set` with a later TTL in the meantime.
+    pub fn with_eviction(interval: Duration) -> Self {
+        let tempo = Self::new();
+        let store = Arc::clone(&tempo.store);
+
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+            Self::evict_expired(&store, Instant::now());
+        });
+
+        tempo
+    }
+
+    /// Like `new`, but evicts the soonest-to-expire key once `max_capacity` is reached.
+    pub fn with_capacity(max_capacity: usize) -> Self {
+        let tempo = Self::new();
+        tempo.store.lock().unwrap().max_capacity = Some(max_capacity);
+        tempo
+    }
+
+    fn evict_expired(store: &Arc<Mutex<Store<T>>>, now: Instant) {
+        let mut store = store.lock().unwrap();
+        while let Some(Reverse((expire_at, _))) = store.heap.peek() {
+            if *expire_at > now {
+                break;
+            }
+            let Reverse((expire_at, key)) = store.heap.pop().unwrap();
+            if store.map.get(&key) == Some(&expire_at) {
+                store.map.remove(&key);
+            }
+        }
+    }
+
+    pub fn set(&self, key: T, ttl: Duration) {
+        let expire_at = Instant::now().add(ttl);
         let mut store = self.store.lock().unwrap();
-        store.insert(key, expire_in);
+
+        if let Some(max_capacity) = store.max_capacity {
+            while store.map.len() >= max_capacity && !store.map.contains_key(&key) {
+                match store.heap.pop() {
+                    Some(Reverse((expire_at, soonest))) => {
+                        if store.map.get(&soonest) == Some(&expire_at) {
+                            store.map.remove(&soonest);
+                        }
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        store.map.insert(key.clone(), expire_at);
+        store.heap.push(Reverse((expire_at, key)));
     }
 
-    pub fn exists(&mut self, key: T) -> bool {
+    pub fn exists(&self, key: &T) -> bool {
         let mut store = self.store.lock().unwrap();
-        let res = store.get(&key);
-        match res {
-            Some(expire_in) => {
-                let now = Instant::now();
-                if expire_in.le(&now) {
-                    store.remove(&key);
-                    return false;
+        match store.map.get(key) {
+            Some(expire_at) => {
+                if *expire_at <= Instant::now() {
+                    store.map.remove(key);
+                    false
+                } else {
+                    true
                 }
-                return true;
             }
-            None => return false,
-        };
+            None => false,
+        }
     }
-}
\ No newline at end of file
+}
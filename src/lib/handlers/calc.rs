@@ -0,0 +1,109 @@
+use crate::client;
+use crate::handlers::{Handler, Result};
+use crate::models::GenericPost;
+use regex::Regex;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a user's last result stays available as `ans` before it expires.
+const MEMORY_TTL: Duration = Duration::from_secs(3600);
+
+/// How often the janitor thread sweeps expired `ans` entries out of `memory`,
+/// so an abandoned `(team, user)` key doesn't linger forever.
+const MEMORY_SWEEP_INTERVAL: Duration = Duration::from_secs(600);
+
+type Memory = Arc<Mutex<HashMap<(String, String), (f64, Instant)>>>;
+
+pub struct Calc<C> {
+    client: Rc<C>,
+    match_calc: Regex,
+    memory: Memory,
+}
+
+impl<C> Calc<C> {
+    pub fn new(client: Rc<C>) -> Self {
+        let memory: Memory = Arc::new(Mutex::new(HashMap::new()));
+        Self::spawn_janitor(Arc::clone(&memory));
+
+        Self {
+            client,
+            match_calc: Regex::new("^!calc (.+)$").unwrap(),
+            memory,
+        }
+    }
+
+    /// Mirrors `Tempo::with_eviction`: wake on an interval and drop whatever
+    /// expired, instead of relying on the owning user running `!calc` again.
+    fn spawn_janitor(memory: Memory) {
+        thread::spawn(move || loop {
+            thread::sleep(MEMORY_SWEEP_INTERVAL);
+            let now = Instant::now();
+            memory.lock().unwrap().retain(|_, (_, expire_at)| *expire_at > now);
+        });
+    }
+
+    fn last_answer(&self, team_id: &str, user_id: &str) -> Option<f64> {
+        let key = (team_id.to_string(), user_id.to_string());
+        let mut memory = self.memory.lock().unwrap();
+        match memory.get(&key) {
+            Some((value, expire_at)) if *expire_at > Instant::now() => Some(*value),
+            Some(_) => {
+                memory.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn remember(&self, team_id: &str, user_id: &str, value: f64) {
+        let key = (team_id.to_string(), user_id.to_string());
+        let mut memory = self.memory.lock().unwrap();
+        memory.insert(key, (value, Instant::now() + MEMORY_TTL));
+    }
+}
+
+impl<C> Handler for Calc<C>
+where
+    C: client::Sender,
+{
+    type Data = GenericPost;
+
+    fn name(&self) -> String {
+        "calc".to_string()
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(String::from(
+            "```
+!calc <expression>   evaluate a math expression, e.g. !calc 2 + 2 * 6
+
+Your last result is kept around for a while as `ans`, so you can chain off it:
+!calc ans * 2
+```",
+        ))
+    }
+
+    fn handle(&self, post: &GenericPost) -> Result {
+        let expr = match self.match_calc.captures(post.message.as_str()) {
+            Some(captures) => captures.get(1).unwrap().as_str(),
+            None => return Ok(()),
+        };
+
+        let mut ctx = meval::Context::new();
+        if let Some(ans) = self.last_answer(&post.team_id, &post.user_id) {
+            ctx.var("ans", ans);
+        }
+
+        match meval::eval_str_with_context(expr, &ctx) {
+            Ok(value) => {
+                self.remember(&post.team_id, &post.user_id, value);
+                Ok(self.client.reply(post, &value.to_string())?)
+            }
+            // mirror Trigger::handle: surface the error instead of dropping it.
+            Err(e) => Ok(self.client.reply(post, &e.to_string())?),
+        }
+    }
+}
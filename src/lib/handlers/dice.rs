@@ -0,0 +1,423 @@
+use crate::client;
+use crate::handlers::{Handler, Result};
+use crate::models::GenericPost;
+use rand::Rng;
+use regex::Regex;
+use std::rc::Rc;
+
+const MAX_DICE: u32 = 100;
+const MAX_SIDES: u32 = 1000;
+
+#[derive(Debug)]
+pub enum ParseError {
+    Empty,
+    UnexpectedChar(char),
+    UnexpectedEnd,
+    TooManyDice(u32),
+    TooManySides(u32),
+    ZeroSides,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "gimme something to roll, e.g. `2d6+3`"),
+            ParseError::UnexpectedChar(c) => write!(f, "unexpected character '{}' in dice expression", c),
+            ParseError::UnexpectedEnd => write!(f, "dice expression ended unexpectedly"),
+            ParseError::TooManyDice(n) => write!(f, "can't roll {} dice at once (max {})", n, MAX_DICE),
+            ParseError::TooManySides(n) => write!(f, "a die can't have {} sides (max {})", n, MAX_SIDES),
+            ParseError::ZeroSides => write!(f, "a die needs at least 1 side"),
+        }
+    }
+}
+
+enum Keep {
+    Highest(u32),
+    Lowest(u32),
+}
+
+enum Term {
+    Constant(i64),
+    Dice {
+        count: u32,
+        sides: u32,
+        keep: Option<Keep>,
+    },
+}
+
+/// Recursive-descent parser for `term (('+'|'-') term)*`, where a term is either
+/// a bare integer or `NdM` dice notation with an optional `kh<k>`/`kl<k>` modifier.
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            chars: input.chars().peekable(),
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.chars.peek(), Some(c) if c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    fn parse_number(&mut self) -> std::result::Result<u32, ParseError> {
+        let mut digits = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            digits.push(c);
+            self.chars.next();
+        }
+        digits.parse().map_err(|_| ParseError::UnexpectedEnd)
+    }
+
+    fn parse_keep(&mut self) -> std::result::Result<Option<Keep>, ParseError> {
+        let mut lookahead = self.chars.clone();
+        match (lookahead.next(), lookahead.next()) {
+            (Some('k'), Some('h')) => {
+                self.chars = lookahead;
+                Ok(Some(Keep::Highest(self.parse_number()?)))
+            }
+            (Some('k'), Some('l')) => {
+                self.chars = lookahead;
+                Ok(Some(Keep::Lowest(self.parse_number()?)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_term(&mut self) -> std::result::Result<Term, ParseError> {
+        self.skip_ws();
+
+        let has_leading_digit = matches!(self.chars.peek(), Some(c) if c.is_ascii_digit());
+        let leading = if has_leading_digit {
+            self.parse_number()?
+        } else {
+            0
+        };
+
+        if matches!(self.chars.peek(), Some('d') | Some('D')) {
+            self.chars.next();
+            let count = if has_leading_digit { leading } else { 1 };
+            let sides = self.parse_number()?;
+
+            if sides == 0 {
+                return Err(ParseError::ZeroSides);
+            }
+            if count > MAX_DICE {
+                return Err(ParseError::TooManyDice(count));
+            }
+            if sides > MAX_SIDES {
+                return Err(ParseError::TooManySides(sides));
+            }
+
+            let keep = self.parse_keep()?;
+            return Ok(Term::Dice { count, sides, keep });
+        }
+
+        if !has_leading_digit {
+            return Err(match self.chars.peek() {
+                Some(&c) => ParseError::UnexpectedChar(c),
+                None => ParseError::UnexpectedEnd,
+            });
+        }
+
+        Ok(Term::Constant(leading as i64))
+    }
+
+    fn parse_expr(&mut self) -> std::result::Result<Vec<(i64, Term)>, ParseError> {
+        let mut terms = vec![(1, self.parse_term()?)];
+
+        loop {
+            self.skip_ws();
+            match self.chars.peek() {
+                Some('+') => {
+                    self.chars.next();
+                    terms.push((1, self.parse_term()?));
+                }
+                Some('-') => {
+                    self.chars.next();
+                    terms.push((-1, self.parse_term()?));
+                }
+                Some(&c) => return Err(ParseError::UnexpectedChar(c)),
+                None => break,
+            }
+        }
+
+        Ok(terms)
+    }
+}
+
+fn parse(input: &str) -> std::result::Result<Vec<(i64, Term)>, ParseError> {
+    if input.trim().is_empty() {
+        return Err(ParseError::Empty);
+    }
+    Parser::new(input.trim()).parse_expr()
+}
+
+/// Which rolled indices count toward the total for a given `kh`/`kl` modifier
+/// (or all of them, for a plain `NdM`).
+fn kept_indices(rolls: &[i64], keep: &Option<Keep>) -> std::collections::HashSet<usize> {
+    match keep {
+        Some(Keep::Highest(k)) => {
+            let mut order: Vec<usize> = (0..rolls.len()).collect();
+            order.sort_unstable_by(|&a, &b| rolls[b].cmp(&rolls[a]));
+            order.into_iter().take(*k as usize).collect()
+        }
+        Some(Keep::Lowest(k)) => {
+            let mut order: Vec<usize> = (0..rolls.len()).collect();
+            order.sort_unstable_by(|&a, &b| rolls[a].cmp(&rolls[b]));
+            order.into_iter().take(*k as usize).collect()
+        }
+        None => (0..rolls.len()).collect(),
+    }
+}
+
+/// Render `rolls` with dropped dice struck through, so the printed numbers
+/// reconcile with whatever `kept_indices` fed into the total.
+fn format_rolls(rolls: &[i64], kept: &std::collections::HashSet<usize>) -> String {
+    format!(
+        "[{}]",
+        rolls
+            .iter()
+            .enumerate()
+            .map(|(i, r)| if kept.contains(&i) {
+                r.to_string()
+            } else {
+                format!("~{}~", r)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+pub struct Rolled {
+    pub total: i64,
+    pub detail: String,
+}
+
+pub fn roll(input: &str) -> std::result::Result<Rolled, ParseError> {
+    let terms = parse(input)?;
+    let mut rng = rand::thread_rng();
+    let mut total: i64 = 0;
+    let mut detail = String::new();
+
+    for (i, (sign, term)) in terms.iter().enumerate() {
+        let op = match (i, sign) {
+            (0, -1) => "-",
+            (0, _) => "",
+            (_, -1) => " - ",
+            (_, _) => " + ",
+        };
+        detail.push_str(op);
+
+        match term {
+            Term::Constant(value) => {
+                total += sign * value;
+                detail.push_str(&value.to_string());
+            }
+            Term::Dice { count, sides, keep } => {
+                let rolls: Vec<i64> = (0..*count).map(|_| rng.gen_range(1..=*sides as i64)).collect();
+                let kept = kept_indices(&rolls, keep);
+
+                total += sign * kept.iter().map(|&i| rolls[i]).sum::<i64>();
+                detail.push_str(&format_rolls(&rolls, &kept));
+            }
+        }
+    }
+
+    Ok(Rolled { total, detail })
+}
+
+pub struct Dice<C> {
+    client: Rc<C>,
+    match_roll: Regex,
+}
+
+impl<C> Dice<C> {
+    pub fn new(client: Rc<C>) -> Self {
+        Self {
+            client,
+            match_roll: Regex::new("^!roll (.+)$").unwrap(),
+        }
+    }
+}
+
+impl<C> Handler for Dice<C>
+where
+    C: client::Sender,
+{
+    type Data = GenericPost;
+
+    fn name(&self) -> String {
+        "dice".to_string()
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(String::from(
+            "```
+!roll <expr>   roll standard RPG dice notation, e.g. !roll 2d6+3 or !roll 4d6kh3
+```",
+        ))
+    }
+
+    fn handle(&self, post: &GenericPost) -> Result {
+        let expr = match self.match_roll.captures(post.message.as_str()) {
+            Some(captures) => captures.get(1).unwrap().as_str(),
+            None => return Ok(()),
+        };
+
+        match roll(expr) {
+            Ok(rolled) => Ok(self
+                .client
+                .reply(post, &format!("{} → {} = {}", expr.trim(), rolled.detail, rolled.total))?),
+            // a typo shouldn't panic the handler thread, so surface it as a normal reply.
+            Err(e) => Ok(self.client.reply(post, &e.to_string())?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term_sign(input: &str, i: usize) -> i64 {
+        parse(input).unwrap()[i].0
+    }
+
+    #[test]
+    fn parses_bare_constant() {
+        let terms = parse("5").unwrap();
+        assert_eq!(terms.len(), 1);
+        assert!(matches!(terms[0], (1, Term::Constant(5))));
+    }
+
+    #[test]
+    fn parses_ndm_dice() {
+        let terms = parse("2d6").unwrap();
+        assert!(matches!(
+            terms[0],
+            (
+                1,
+                Term::Dice {
+                    count: 2,
+                    sides: 6,
+                    keep: None
+                }
+            )
+        ));
+    }
+
+    #[test]
+    fn parses_bare_d_as_a_single_die() {
+        let terms = parse("d20").unwrap();
+        assert!(matches!(
+            terms[0],
+            (
+                1,
+                Term::Dice {
+                    count: 1,
+                    sides: 20,
+                    keep: None
+                }
+            )
+        ));
+    }
+
+    #[test]
+    fn parses_plus_and_minus_chain() {
+        let terms = parse("2d6+3-1").unwrap();
+        assert_eq!(terms.len(), 3);
+        assert_eq!(term_sign("2d6+3-1", 1), 1);
+        assert_eq!(term_sign("2d6+3-1", 2), -1);
+    }
+
+    #[test]
+    fn parses_keep_highest_and_lowest() {
+        let terms = parse("4d6kh3").unwrap();
+        assert!(matches!(
+            terms[0].1,
+            Term::Dice {
+                keep: Some(Keep::Highest(3)),
+                ..
+            }
+        ));
+
+        let terms = parse("4d6kl2").unwrap();
+        assert!(matches!(
+            terms[0].1,
+            Term::Dice {
+                keep: Some(Keep::Lowest(2)),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn rejects_too_many_dice() {
+        assert!(matches!(parse("101d6"), Err(ParseError::TooManyDice(101))));
+    }
+
+    #[test]
+    fn rejects_too_many_sides() {
+        assert!(matches!(parse("1d1001"), Err(ParseError::TooManySides(1001))));
+    }
+
+    #[test]
+    fn rejects_zero_sides() {
+        assert!(matches!(parse("1d0"), Err(ParseError::ZeroSides)));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(parse("d").is_err());
+        assert!(parse("2d").is_err());
+        assert!(parse("kh").is_err());
+        assert!(parse("2d6++").is_err());
+        assert!(matches!(parse(""), Err(ParseError::Empty)));
+    }
+
+    #[test]
+    fn roll_of_a_bare_constant_is_deterministic() {
+        let rolled = roll("7").unwrap();
+        assert_eq!(rolled.total, 7);
+        assert_eq!(rolled.detail, "7");
+    }
+
+    #[test]
+    fn roll_total_stays_within_the_dice_bounds() {
+        let rolled = roll("3d6+2").unwrap();
+        assert!(rolled.total >= 3 + 2 && rolled.total <= 18 + 2);
+    }
+
+    #[test]
+    fn kept_indices_keeps_the_highest_k() {
+        let rolls = vec![6, 5, 3, 2];
+        let kept = kept_indices(&rolls, &Some(Keep::Highest(3)));
+        assert_eq!(kept.len(), 3);
+        assert!(!kept.contains(&3)); // the lowest roll (2) is dropped
+    }
+
+    #[test]
+    fn kept_indices_keeps_the_lowest_k() {
+        let rolls = vec![6, 5, 3, 2];
+        let kept = kept_indices(&rolls, &Some(Keep::Lowest(2)));
+        assert_eq!(kept.len(), 2);
+        assert!(kept.contains(&2) && kept.contains(&3));
+    }
+
+    #[test]
+    fn format_rolls_marks_dropped_dice_and_reconciles_with_the_total() {
+        let rolls = vec![6, 5, 3, 2];
+        let kept = kept_indices(&rolls, &Some(Keep::Highest(3)));
+        assert_eq!(format_rolls(&rolls, &kept), "[6, 5, 3, ~2~]");
+
+        let total: i64 = kept.iter().map(|&i| rolls[i]).sum();
+        assert_eq!(total, 14);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::client;
+use crate::handlers::{Handler, Result};
+use crate::models::GenericPost;
+use rand::Rng;
+use regex::Regex;
+use std::rc::Rc;
+
+/// Replies longer than this (in bytes) are left untouched instead of mangled,
+/// so a pasted wall of text can't be turned into a giant reply.
+const MAX_LEN: usize = 512;
+
+const KAOMOJIS: &[&str] = &["(・ω・)", "(*^ω^*)", "UwU", ">w<", "(^• ω •^)"];
+
+pub struct Leek<C> {
+    client: Rc<C>,
+    match_mock: Regex,
+    match_leet: Regex,
+    match_owo: Regex,
+}
+
+impl<C> Leek<C> {
+    pub fn new(client: Rc<C>) -> Self {
+        Self {
+            client,
+            match_mock: Regex::new("^!mock (.+)$").unwrap(),
+            match_leet: Regex::new("^!leet (.+)$").unwrap(),
+            match_owo: Regex::new("^!owo (.+)$").unwrap(),
+        }
+    }
+}
+
+fn mock(text: &str) -> String {
+    let mut rng = rand::thread_rng();
+    text.chars()
+        .map(|c| {
+            if !c.is_alphabetic() {
+                return c;
+            }
+            if rng.gen_bool(0.5) {
+                c.to_ascii_uppercase()
+            } else {
+                c.to_ascii_lowercase()
+            }
+        })
+        .collect()
+}
+
+fn leet(text: &str) -> String {
+    text.chars()
+        .map(|c| match c.to_ascii_lowercase() {
+            'a' => '4',
+            'e' => '3',
+            'i' => '1',
+            'o' => '0',
+            't' => '7',
+            's' => '5',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Truncate `s` to at most `max_len` bytes, backing off to the nearest char
+/// boundary so a multi-byte kaomoji never gets split.
+fn truncate_to_boundary(s: &str, max_len: usize) -> String {
+    if s.len() <= max_len {
+        return s.to_string();
+    }
+    let mut end = max_len;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s[..end].to_string()
+}
+
+fn owoify(text: &str) -> String {
+    let mut rng = rand::thread_rng();
+
+    let nyaified = Regex::new("n([aeiouAEIOU])").unwrap().replace_all(text, "ny$1").into_owned();
+
+    let mut owo: String = nyaified
+        .chars()
+        .map(|c| match c {
+            'r' | 'l' => 'w',
+            'R' | 'L' => 'W',
+            other => other,
+        })
+        .collect();
+
+    if rng.gen_bool(0.3) {
+        if let Some(first_space) = owo.find(' ') {
+            let stutter = owo[..first_space].to_string();
+            owo.insert_str(0, &format!("{}-", stutter));
+        }
+    }
+
+    // occasionally, not always, tack on a kaomoji suffix.
+    if rng.gen_bool(0.3) {
+        owo.push(' ');
+        owo.push_str(KAOMOJIS[rng.gen_range(0..KAOMOJIS.len())]);
+    }
+
+    if owo.len() > MAX_LEN {
+        return text.to_string();
+    }
+    owo
+}
+
+impl<C> Handler for Leek<C>
+where
+    C: client::Sender,
+{
+    type Data = GenericPost;
+
+    fn name(&self) -> String {
+        "leek".to_string()
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(String::from(
+            "```
+!mock <text>    make fUn oF yOuR tExT
+!leet <text>    turn your text into l33tspeak
+!owo <text>     owoify your text
+```",
+        ))
+    }
+
+    fn handle(&self, post: &GenericPost) -> Result {
+        let message = post.message.as_str();
+
+        let (captures, transform): (regex::Captures, fn(&str) -> String) =
+            if let Some(c) = self.match_mock.captures(message) {
+                (c, mock)
+            } else if let Some(c) = self.match_leet.captures(message) {
+                (c, leet)
+            } else if let Some(c) = self.match_owo.captures(message) {
+                (c, owoify)
+            } else {
+                return Ok(());
+            };
+
+        let text = captures.get(1).unwrap().as_str();
+        if text.len() > MAX_LEN {
+            // a wall of text comes back untouched rather than blowing up the reply.
+            return Ok(self.client.reply(post, text)?);
+        }
+
+        Ok(self.client.reply(post, &transform(text))?)
+    }
+}
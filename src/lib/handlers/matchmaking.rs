@@ -0,0 +1,90 @@
+use crate::client;
+use crate::db;
+use crate::handlers::{Handler, Result};
+use crate::models::GenericPost;
+use regex::Regex;
+use std::rc::Rc;
+
+/// Reaction added to the bot's own announcement; other users reacting the
+/// same way join the roster.
+const JOIN_REACTION: &str = "raised_hand";
+
+/// A match starts early once this many people have joined, without waiting for `start_at`.
+pub const QUORUM: usize = 3;
+
+pub struct Matchmaking<C, E> {
+    db: Rc<E>,
+    client: Rc<C>,
+    match_new: Regex,
+}
+
+impl<C, E> Matchmaking<C, E> {
+    pub fn new(db: Rc<E>, client: Rc<C>) -> Self {
+        Self {
+            db,
+            client,
+            match_new: Regex::new("^!match \"([^\"]+)\" in (\\S+)$").unwrap(),
+        }
+    }
+}
+
+impl<C, E> Handler for Matchmaking<C, E>
+where
+    C: client::Sender,
+    E: db::Matches,
+{
+    type Data = GenericPost;
+
+    fn name(&self) -> String {
+        "matchmaking".to_string()
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(format!(
+            "```
+!match \"<activity>\" in <duration>   e.g. !match \"board games\" in 30m
+
+React with :{}: on the announcement to join. The match pings everyone once
+{} people have joined, or when the timer runs out — whichever comes first.
+```",
+            JOIN_REACTION, QUORUM
+        ))
+    }
+
+    fn handle(&self, post: &GenericPost) -> Result {
+        let captures = match self.match_new.captures(post.message.as_str()) {
+            Some(captures) => captures,
+            None => return Ok(()),
+        };
+
+        let activity = captures.get(1).unwrap().as_str();
+        let duration = captures.get(2).unwrap().as_str();
+
+        let parsed = match humantime::parse_duration(duration) {
+            Ok(d) => d,
+            Err(e) => return Ok(self.client.reply(post, &e.to_string())?),
+        };
+        let start_at =
+            chrono::Utc::now().naive_utc() + chrono::Duration::from_std(parsed).unwrap_or_else(|_| chrono::Duration::zero());
+
+        let announcement = self.client.post(
+            &post.channel_id,
+            &format!(
+                "\u{1F4E3} {} is starting in {} — react with :{}: to join!",
+                activity, duration, JOIN_REACTION
+            ),
+        )?;
+        self.client.reaction(&announcement, JOIN_REACTION)?;
+
+        self.db.create(
+            &announcement.id,
+            &post.team_id,
+            &post.channel_id,
+            activity,
+            start_at,
+            &announcement.user_id,
+        )?;
+
+        Ok(())
+    }
+}
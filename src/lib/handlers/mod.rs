@@ -0,0 +1,55 @@
+pub mod calc;
+pub mod command;
+pub mod dice;
+pub mod leek;
+pub mod matchmaking;
+pub mod remind;
+pub mod trigger;
+
+use crate::client;
+use crate::db;
+use std::convert::From;
+
+#[derive(Debug)]
+pub enum Error {
+    Client(client::Error),
+    Database(db::Error),
+}
+
+impl std::error::Error for Error {}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "handlers::Error: {:?}", self)
+    }
+}
+
+impl From<client::Error> for Error {
+    fn from(e: client::Error) -> Self {
+        Error::Client(e)
+    }
+}
+
+impl From<db::Error> for Error {
+    fn from(e: db::Error) -> Self {
+        Error::Database(e)
+    }
+}
+
+pub type Result = std::result::Result<(), Error>;
+
+pub trait Handler {
+    type Data;
+    fn name(&self) -> String;
+    fn help(&self) -> Option<String>;
+    fn handle(&self, data: &Self::Data) -> Result;
+
+    /// Serialize any in-memory state that should survive a restart. Stateless
+    /// handlers (most of them) can leave this at its default of `None`.
+    fn freeze(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Restore state previously returned by `freeze`. No-op by default.
+    fn thaw(&mut self, _state: &[u8]) {}
+}
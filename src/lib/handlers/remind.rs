@@ -0,0 +1,94 @@
+use crate::client;
+use crate::db;
+use crate::handlers::{Handler, Result};
+use crate::models::GenericPost;
+use regex::Regex;
+use std::rc::Rc;
+
+pub struct Remind<C, E> {
+    db: Rc<E>,
+    client: Rc<C>,
+    match_add: Regex,
+    match_list: Regex,
+    match_del: Regex,
+}
+
+impl<C, E> Remind<C, E> {
+    pub fn new(db: Rc<E>, client: Rc<C>) -> Self {
+        Self {
+            db,
+            client,
+            match_add: Regex::new(r"^!remind me in (\S+) (.+)$").unwrap(),
+            match_list: Regex::new(r"^!remind list.*$").unwrap(),
+            match_del: Regex::new(r"^!remind del (\d+).*$").unwrap(),
+        }
+    }
+}
+
+impl<C, E> Handler for Remind<C, E>
+where
+    C: client::Sender,
+    E: db::Reminders,
+{
+    type Data = GenericPost;
+
+    fn name(&self) -> String {
+        "remind".to_string()
+    }
+
+    fn help(&self) -> Option<String> {
+        Some(String::from(
+            "```
+!remind me in <duration> <message>   e.g. !remind me in 2h30m stretch your legs
+!remind list                         list your pending reminders
+!remind del <id>                     cancel a reminder
+```",
+        ))
+    }
+
+    fn handle(&self, post: &GenericPost) -> Result {
+        let message = post.message.as_str();
+
+        if let Some(captures) = self.match_add.captures(message) {
+            let duration = captures.get(1).unwrap().as_str();
+            let text = captures.get(2).unwrap().as_str();
+
+            return match humantime::parse_duration(duration) {
+                Ok(d) => {
+                    let remind_at = chrono::Utc::now().naive_utc()
+                        + chrono::Duration::from_std(d).unwrap_or_else(|_| chrono::Duration::zero());
+                    self.db.add(&post.team_id, &post.channel_id, &post.user_id, remind_at, text)?;
+                    Ok(self.client.reaction(post, "ok_hand")?)
+                }
+                // a bad duration shouldn't vanish silently, mirror Trigger's error replies.
+                Err(e) => Ok(self.client.reply(post, &e.to_string())?),
+            };
+        }
+
+        if self.match_list.is_match(message) {
+            let reminders = self.db.list(&post.team_id, &post.user_id)?;
+            if reminders.is_empty() {
+                return Ok(self.client.reply(post, "you have no pending reminders")?);
+            }
+
+            let mut reply = String::new();
+            for r in reminders.iter() {
+                reply.push_str(&format!("`{}` at {} UTC: {}\n", r.id, r.remind_at, r.message));
+            }
+            return Ok(self.client.reply(post, &reply)?);
+        }
+
+        if let Some(captures) = self.match_del.captures(message) {
+            let id_str = captures.get(1).unwrap().as_str();
+            return match id_str.parse::<i32>() {
+                Ok(id) => {
+                    self.db.del(id)?;
+                    Ok(self.client.reaction(post, "ok_hand")?)
+                }
+                Err(_) => Ok(self.client.reply(post, &format!("no such reminder: {}", id_str))?),
+            };
+        }
+
+        Ok(())
+    }
+}
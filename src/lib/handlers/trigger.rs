@@ -1,6 +1,7 @@
 use crate::client;
 use crate::db;
 use crate::db::tempo::Tempo;
+use crate::handlers::command::{Arg, Command, Outcome, Spec};
 use crate::handlers::{Handler, Result};
 use crate::models::GenericPost;
 use crate::models::Trigger as MTrigger;
@@ -38,28 +39,84 @@ pub fn valid_match(find: &str, message: &str) -> bool {
     true
 }
 
+fn build_command<C, E>(db: &Rc<E>, client: &Rc<C>) -> Command<GenericPost>
+where
+    C: client::Sender + 'static,
+    E: db::Trigger + 'static,
+{
+    Command::new("!trigger")
+        .on("list", vec![], {
+            let db = Rc::clone(db);
+            let client = Rc::clone(client);
+            move |post: &GenericPost, _args: &[Arg]| {
+                let res = db.list(&post.team_id)?;
+                Ok(client.send_trigger_list(res, post)?)
+            }
+        })
+        .on("text", vec![Spec::QuotedString, Spec::QuotedString], {
+            let db = Rc::clone(db);
+            let client = Rc::clone(client);
+            move |post: &GenericPost, args: &[Arg]| {
+                let trigger = args[0].as_str();
+                let text = args[1].as_str();
+
+                // prevent insertion of broken triggers.
+                if let Err(e) = compile_trigger(trigger) {
+                    return Ok(client.reply(post, &e.to_string())?);
+                }
+
+                let _ = db.add_text(&post.team_id, trigger, text);
+                Ok(client.reaction(post, "ok_hand")?)
+            }
+        })
+        .on("reaction", vec![Spec::QuotedString, Spec::Word], {
+            let db = Rc::clone(db);
+            let client = Rc::clone(client);
+            move |post: &GenericPost, args: &[Arg]| {
+                let trigger = args[0].as_str();
+                let emoji = args[1].as_str().trim_matches(':');
+
+                // prevent insertion of broken triggers.
+                if let Err(e) = compile_trigger(trigger) {
+                    return Ok(client.reply(post, &e.to_string())?);
+                }
+
+                let _ = db.add_emoji(&post.team_id, trigger, emoji);
+                Ok(client.reaction(post, "ok_hand")?)
+            }
+        })
+        .on("del", vec![Spec::QuotedString], {
+            let db = Rc::clone(db);
+            let client = Rc::clone(client);
+            move |post: &GenericPost, args: &[Arg]| {
+                let trigger = args[0].as_str();
+                let _ = db.del(&post.team_id, trigger)?;
+                Ok(client.reaction(post, "ok_hand")?)
+            }
+        })
+}
+
 pub struct Trigger<C, E> {
     db: Rc<E>,
     client: Rc<C>,
-    match_list: Regex,
-    match_del: Regex,
-    match_text: Regex,
-    match_reaction: Regex,
+    command: Command<GenericPost>,
     tempo: Tempo<String>,
     delay_repeat: Duration,
 }
 
-impl<C, E> Trigger<C, E> {
+impl<C, E> Trigger<C, E>
+where
+    C: client::Sender + 'static,
+    E: db::Trigger + 'static,
+{
     pub fn new(db: Rc<E>, client: Rc<C>, tempo: Tempo<String>, delay_repeat: Duration) -> Self {
+        let command = build_command(&db, &client);
         Self {
             db,
             client,
+            command,
             tempo,
             delay_repeat,
-            match_list: Regex::new("^!trigger list.*$").unwrap(),
-            match_del: Regex::new("^!trigger del \"(.+)\".*").unwrap(),
-            match_reaction: Regex::new("^!trigger reaction \"([^\"]+)\" [:\"]([^:]+)[:\"].*$").unwrap(),
-            match_text: Regex::new("^!trigger text \"([^\"]+)\" \"([^\"]+)\".*$").unwrap(),
         }
     }
 
@@ -70,30 +127,22 @@ impl<C, E> Trigger<C, E> {
 
 impl<C, E> Handler for Trigger<C, E>
 where
-    C: client::Sender,
-    E: db::Trigger,
+    C: client::Sender + 'static,
+    E: db::Trigger + 'static,
 {
     type Data = GenericPost;
 
-    fn name(&self) -> &str {
-        "trigger"
+    fn name(&self) -> String {
+        "trigger".to_string()
     }
 
     fn help(&self) -> Option<String> {
         Some(format!(
-            "```
-Automatically react to a given text in each received message on channels where the bot is present.
-
-There is a per channel antispam of 3 seconds, avoiding a heated channel to be polluted by the bot.
-
-A per [channel, trigger] antispam is effective and currently configured at {} seconds.
-
-!trigger list
-!trigger text \"trigger\" \"me\"
-!trigger reaction \"trigger\" :emoji:
-!trigger del \"trigger\"
-```",
-            self.delay_repeat.as_secs()
+            "Automatically react to a given text in each received message on channels where the bot is present.\n\n\
+There is a per channel antispam of 3 seconds, avoiding a heated channel to be polluted by the bot.\n\n\
+A per [channel, trigger] antispam is effective and currently configured at {} seconds.\n\n{}",
+            self.delay_repeat.as_secs(),
+            self.command.help()
         ))
     }
 
@@ -138,50 +187,10 @@ A per [channel, trigger] antispam is effective and currently configured at {} se
             return Ok(());
         }
 
-        if self.match_list.is_match(message) {
-            let res = self.db.list(&post.team_id)?;
-            return Ok(self.client.send_trigger_list(res, post)?);
+        match self.command.dispatch(message, post) {
+            Outcome::Handled(res) => res,
+            Outcome::Usage(usage) => Ok(self.client.reply(post, &usage)?),
         }
-
-        match self.match_text.captures(message) {
-            Some(captures) => {
-                let trigger = captures.get(1).unwrap().as_str();
-
-                // prevent insertion of broken triggers.
-                if let Err(e) = compile_trigger(trigger) {
-                    return Ok(self.client.reply(post, &e.to_string())?);
-                }
-
-                let _ = self.db.add_text(&post.team_id, trigger, captures.get(2).unwrap().as_str());
-                return Ok(self.client.reaction(post, "ok_hand")?);
-            }
-            None => {}
-        }
-
-        match self.match_reaction.captures(message) {
-            Some(captures) => {
-                let trigger = captures.get(1).unwrap().as_str();
-
-                // prevent insertion of broken triggers.
-                if let Err(e) = compile_trigger(trigger) {
-                    return Ok(self.client.reply(post, &e.to_string())?);
-                }
-
-                let _ = self.db.add_emoji(&post.team_id, trigger, captures.get(2).unwrap().as_str());
-                return Ok(self.client.reaction(post, "ok_hand")?);
-            }
-            None => {}
-        }
-
-        match self.match_del.captures(message) {
-            Some(captures) => {
-                let _ = self.db.del(&post.team_id, captures.get(1).unwrap().as_str())?;
-                return Ok(self.client.reaction(post, "ok_hand")?);
-            }
-            None => {}
-        }
-
-        Ok(())
     }
 }
 
@@ -4,20 +4,121 @@ use crate::middleware::Continue;
 use crate::middleware::Error as MiddlewareError;
 use crate::middleware::Middleware as MMiddleware;
 use crate::models::{Event, Post, StatusCode, StatusError};
-use crossbeam_channel::{Receiver, RecvTimeoutError};
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender};
+use std::collections::HashMap;
 use std::convert::From;
+use std::sync::{Arc, Mutex};
 
 use std::time::Duration;
 
+/// Running totals on the event queue, queryable through `!stats`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Counters {
+    pub received: u64,
+    pub processed: u64,
+    pub dropped: u64,
+}
+
+/// Producer-side handle onto `Instance`'s bounded event channel. The websocket
+/// reader thread holds one of these instead of a raw `crossbeam_channel::Sender`,
+/// so it can shed low-priority events (e.g. `typing`) once the queue is nearly
+/// full instead of blocking or growing without bound.
+#[derive(Clone)]
+pub struct EventSender {
+    inner: Sender<Event>,
+    counters: Arc<Mutex<Counters>>,
+}
+
+impl EventSender {
+    /// `typing` updates are purely cosmetic; they're the first thing to shed
+    /// under backpressure since losing one doesn't lose any real information.
+    fn is_low_priority(event: &Event) -> bool {
+        matches!(event, Event::Typing(_))
+    }
+
+    pub fn send(&self, event: Event) {
+        let mut counters = self.counters.lock().unwrap();
+        counters.received += 1;
+
+        let nearly_full = self
+            .inner
+            .capacity()
+            .map_or(false, |capacity| self.inner.len() + 1 >= capacity);
+
+        if nearly_full && Self::is_low_priority(&event) {
+            counters.dropped += 1;
+            return;
+        }
+        drop(counters);
+
+        let _ = self.inner.send(event);
+    }
+}
+
+/// Websocket event kinds a handler can subscribe to individually, instead of
+/// being called on every event. `Post` keeps going through `post_handlers`
+/// for backwards compatibility; everything chunk1-1 newly exposes subscribes
+/// through here.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum EventKind {
+    PostEdited,
+    PostDeleted,
+    ReactionAdded,
+    ReactionRemoved,
+    Typing,
+    ChannelViewed,
+    UserAdded,
+}
+
+impl EventKind {
+    fn of(event: &Event) -> Option<Self> {
+        match event {
+            Event::PostEdited(_) => Some(EventKind::PostEdited),
+            Event::PostDeleted(_) => Some(EventKind::PostDeleted),
+            Event::ReactionAdded(_) => Some(EventKind::ReactionAdded),
+            Event::ReactionRemoved(_) => Some(EventKind::ReactionRemoved),
+            Event::Typing(_) => Some(EventKind::Typing),
+            Event::ChannelViewed(_) => Some(EventKind::ChannelViewed),
+            Event::UserAdded(_) => Some(EventKind::UserAdded),
+            _ => None,
+        }
+    }
+}
+
+pub type EventHandler = Arc<dyn Handler<Data = Event> + Send + Sync>;
+
+/// Whether an `Error` should bring the run loop down or just get logged and
+/// skipped. See `Error::severity`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Severity {
+    Fatal,
+    Recoverable,
+}
+
 #[derive(Debug)]
 pub enum Error {
-    // FIXME: strip down to Fatal and Error
     Other(String),
     Middleware(MiddlewareError),
     Processing(String),
     Client(client::Error),
     Consumer(String),
     Status(String),
+    State(String),
+}
+
+impl Error {
+    /// A dropped consumer channel or an auth/status error means the bot can no
+    /// longer reliably talk to the server, so those are `Fatal`. Everything
+    /// tied to a single handler/middleware invocation or one client call is
+    /// `Recoverable`: log it and keep the loop running.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Error::Consumer(_) | Error::Status(_) | Error::Other(_) => Severity::Fatal,
+            Error::Middleware(_) | Error::Processing(_) | Error::Client(_) | Error::State(_) => {
+                Severity::Recoverable
+            }
+        }
+    }
 }
 
 fn client_err(ce: client::Error) -> Error {
@@ -73,25 +174,63 @@ impl<PH: Handler> Handler for MutexedPostHandler<PH> {
     fn handle(&self, data: &PH::Data) -> crate::handlers::Result {
         self.handler.lock().unwrap().handle(data)
     }
+
+    fn freeze(&self) -> Option<Vec<u8>> {
+        self.handler.lock().unwrap().freeze()
+    }
+
+    fn thaw(&mut self, state: &[u8]) {
+        self.handler.get_mut().unwrap().thaw(state)
+    }
 }
 
 pub struct Instance<C> {
     middlewares: Vec<Middleware>,
     post_handlers: Vec<PostHandler>,
+    handlers: HashMap<EventKind, Vec<EventHandler>>,
     helps: std::collections::HashMap<String, String>,
     client: C,
+    event_sender: EventSender,
+    receiver: Receiver<Event>,
+    counters: Arc<Mutex<Counters>>,
+    /// Queue depth (80% of `capacity`) at which we warn once via `client.debug`.
+    high_water: usize,
+    high_water_crossed: bool,
 }
 
 impl<C: client::Sender + client::Notifier> Instance<C> {
-    pub fn new(client: C) -> Self {
+    /// `capacity` bounds the event channel; once its fill level crosses 80% we
+    /// warn once via `client.debug`. Low-priority events are shed by
+    /// `EventSender::send` only once the queue is one slot short of full.
+    pub fn new(client: C, capacity: usize) -> Self {
+        let (sender, receiver) = bounded(capacity);
+        let counters = Arc::new(Mutex::new(Counters::default()));
         Instance {
             middlewares: Vec::new(),
             post_handlers: Vec::new(),
+            handlers: HashMap::new(),
             helps: std::collections::HashMap::new(),
             client,
+            event_sender: EventSender {
+                inner: sender,
+                counters: Arc::clone(&counters),
+            },
+            receiver,
+            counters,
+            high_water: (capacity * 4 / 5).max(1),
+            high_water_crossed: false,
         }
     }
 
+    /// Handle for the websocket reader thread to push events into this instance.
+    pub fn sender(&self) -> EventSender {
+        self.event_sender.clone()
+    }
+
+    pub fn counters(&self) -> Counters {
+        *self.counters.lock().unwrap()
+    }
+
     pub fn add_middleware(&mut self, middleware: Middleware) -> &mut Self {
         self.middlewares.push(middleware);
         self
@@ -106,6 +245,19 @@ impl<C: client::Sender + client::Notifier> Instance<C> {
         self
     }
 
+    /// Subscribe `handler` to one or more event kinds. Unlike `add_post_handler`,
+    /// which every post reaches unconditionally, a handler registered here is only
+    /// ever called for the kinds it subscribes to.
+    pub fn add_handler(&mut self, kinds: &[EventKind], handler: EventHandler) -> &mut Self {
+        for kind in kinds {
+            self.handlers
+                .entry(*kind)
+                .or_insert_with(Vec::new)
+                .push(Arc::clone(&handler));
+        }
+        self
+    }
+
     fn process_middlewares(&mut self, event: Event) -> Result<Option<Event>, Error> {
         let mut event = event;
         for middleware in self.middlewares.iter() {
@@ -150,8 +302,26 @@ impl<C: client::Sender + client::Notifier> Instance<C> {
         }
     }
 
+    fn process_stats(&self, post: &Post) -> Result<(), Error> {
+        if &post.message != "!stats" {
+            return Ok(());
+        }
+
+        let counters = self.counters();
+        let reply = format!(
+            "```\nreceived:  {}\nprocessed: {}\ndropped:   {}\nqueue depth: {}\n```",
+            counters.received,
+            counters.processed,
+            counters.dropped,
+            self.receiver.len()
+        );
+
+        self.client.reply(post, &reply).map_err(client_err)
+    }
+
     fn process_event_post(&mut self, post: Post) -> Result<(), Error> {
         let _ = self.process_help(&post)?;
+        let _ = self.process_stats(&post)?;
         for handler in self.post_handlers.iter_mut() {
             let res = handler.handle(&post);
             let _ = match res {
@@ -165,13 +335,51 @@ impl<C: client::Sender + client::Notifier> Instance<C> {
         Ok(())
     }
 
+    fn dispatch_subscribers(&mut self, event: &Event) {
+        let kind = match EventKind::of(event) {
+            Some(kind) => kind,
+            None => return,
+        };
+
+        let subscribers = match self.handlers.get(&kind) {
+            Some(subscribers) => subscribers,
+            None => return,
+        };
+
+        for handler in subscribers.iter() {
+            let res = handler.handle(event);
+            let _ = match res {
+                Ok(_) => {}
+                Err(e) => match self.client.debug(&format!("error: {:?}", e)) {
+                    Ok(_) => {}
+                    Err(e) => println!("debug error: {:?}", e),
+                },
+            };
+        }
+    }
+
     fn process_event(&mut self, event: Event) -> Result<(), Error> {
+        self.dispatch_subscribers(&event);
+
         match event {
             Event::Post(post) => self.process_event_post(post),
             Event::PostEdited(_edited) => {
                 println!("edits are unsupported for now");
                 Ok(())
             }
+            Event::PostDeleted(_deleted) => {
+                println!("post deletions are unsupported for now");
+                Ok(())
+            }
+            Event::ReactionAdded(_reaction) => Ok(()),
+            Event::ReactionRemoved(_reaction) => Ok(()),
+            Event::Typing(_typing) => Ok(()),
+            Event::ChannelViewed(_viewed) => Ok(()),
+            Event::UserAdded(_added) => Ok(()),
+            Event::Dynamic(_name, _value) => {
+                //println!("dynamic event: {} {:?}", name, value);
+                Ok(())
+            }
             Event::Unsupported(_unsupported) => {
                 //println!("unsupported event: {:?}", unsupported);
                 Ok(())
@@ -205,7 +413,56 @@ impl<C: client::Sender + client::Notifier> Instance<C> {
         }
     }
 
-    pub fn run(&mut self, receiver: Receiver<Event>) -> Result<(), Error> {
+    /// Write every post handler's `freeze()` blob into a single CBOR map, keyed
+    /// by `handler.name()`, so it can be restored on the next `load_state`.
+    pub fn save_state(&self, path: &str) -> Result<(), Error> {
+        let mut state: HashMap<String, Vec<u8>> = HashMap::new();
+        for handler in self.post_handlers.iter() {
+            if let Some(blob) = handler.freeze() {
+                state.insert(handler.name(), blob);
+            }
+        }
+
+        let file = std::fs::File::create(path).map_err(|e| Error::State(e.to_string()))?;
+        serde_cbor::to_writer(file, &state).map_err(|e| Error::State(e.to_string()))
+    }
+
+    /// Restore handler state previously written by `save_state`. Missing file
+    /// means nothing has ever been saved, which is fine on a first run.
+    pub fn load_state(&mut self, path: &str) -> Result<(), Error> {
+        let file = match std::fs::File::open(path) {
+            Ok(file) => file,
+            Err(_) => return Ok(()),
+        };
+
+        let state: HashMap<String, Vec<u8>> =
+            serde_cbor::from_reader(file).map_err(|e| Error::State(e.to_string()))?;
+
+        for handler in self.post_handlers.iter_mut() {
+            if let Some(blob) = state.get(&handler.name()) {
+                handler.thaw(blob);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Warn once via `client.debug` when the queue crosses `high_water`, and
+    /// clear the flag once it drains back below it so a later burst warns again.
+    fn check_backpressure(&mut self) {
+        let depth = self.receiver.len();
+        if depth >= self.high_water && !self.high_water_crossed {
+            self.high_water_crossed = true;
+            let _ = self.client.debug(&format!(
+                "event queue backpressure: {} events queued (high water {})",
+                depth, self.high_water
+            ));
+        } else if depth < self.high_water {
+            self.high_water_crossed = false;
+        }
+    }
+
+    pub fn run(&mut self, state_path: &str) -> Result<(), Error> {
         let mut loaded = String::from("## Loaded middlewares\n");
         for m in self.middlewares.iter() {
             loaded.push_str(&format!(" * `{}`\n", m.name()));
@@ -217,11 +474,28 @@ impl<C: client::Sender + client::Notifier> Instance<C> {
 
         let _ = self.client.startup(&loaded)?;
 
+        self.load_state(state_path)?;
+
         loop {
-            match receiver.recv_timeout(Duration::from_secs(5)) {
+            self.check_backpressure();
+
+            match self.receiver.recv_timeout(Duration::from_secs(5)) {
                 Ok(e) => match e {
-                    Event::Shutdown => return Ok(()),
-                    _ => self.process(e)?,
+                    Event::Shutdown => {
+                        self.save_state(state_path)?;
+                        return Ok(());
+                    }
+                    _ => {
+                        self.counters.lock().unwrap().processed += 1;
+                        if let Err(e) = self.process(e) {
+                            match e.severity() {
+                                Severity::Fatal => return Err(e),
+                                Severity::Recoverable => {
+                                    let _ = self.client.debug(&format!("recoverable error: {:?}", e));
+                                }
+                            }
+                        }
+                    }
                 },
                 Err(rte) => match rte {
                     RecvTimeoutError::Timeout => {}
@@ -0,0 +1,34 @@
+#[derive(Clone, Debug)]
+pub struct Trigger {
+    pub id: i32,
+    pub team_id: String,
+    pub triggered_by: String,
+    pub text_: Option<String>,
+    pub emoji: Option<String>,
+}
+
+/// A `!remind` entry, persisted so it survives a restart (see `db::Reminders`).
+#[derive(Clone, Debug, Queryable)]
+pub struct Reminder {
+    pub id: i32,
+    pub team_id: String,
+    pub channel_id: String,
+    pub user_id: String,
+    pub remind_at: chrono::NaiveDateTime,
+    pub message: String,
+}
+
+/// An open `!match` RSVP (see `db::Matches`). Participants live in a separate
+/// join table, queried on demand rather than kept inline here.
+#[derive(Clone, Debug, Queryable)]
+pub struct Match {
+    pub id: i32,
+    pub message_id: String,
+    pub team_id: String,
+    pub channel_id: String,
+    pub activity: String,
+    pub start_at: chrono::NaiveDateTime,
+    /// The bot's own user id, so the scheduler can exclude its self-reaction
+    /// on the announcement when syncing the roster.
+    pub bot_user_id: String,
+}
@@ -1,9 +1,15 @@
 use crate::models::Event as GenericEvent;
+use crate::models::GenericChannelViewed;
+use crate::models::GenericPostEdited;
+use crate::models::GenericReaction;
+use crate::models::GenericTyping;
+use crate::models::GenericUserAdded;
 use crate::models::Post as GenericPost;
 use crate::models::Status as GenericStatus;
 use crate::models::StatusCode;
 use crate::models::StatusError as GenericStatusError;
-use serde::{Deserialize, Serialize};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::convert::Into;
 
 #[derive(Deserialize, Serialize)]
@@ -16,6 +22,61 @@ pub struct Posted {
     team_id: String,
 }
 
+/// The shape of the JSON-encoded string carried in `Posted::post` (and in
+/// `PostEdited`/`PostDeleted`'s `post` field). Only the fields we actually use
+/// for threading are listed; serde ignores the rest (`create_at`, `props`, ...).
+#[derive(Deserialize)]
+struct Post {
+    id: String,
+    #[serde(default)]
+    root_id: String,
+    #[serde(default)]
+    parent_id: String,
+    user_id: String,
+    channel_id: String,
+    message: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PostEdited {
+    post: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PostDeleted {
+    post: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Reaction {
+    user_id: String,
+    post_id: String,
+    emoji_name: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Typing {
+    user_id: String,
+    #[serde(default)]
+    parent_id: String,
+    #[serde(default)]
+    channel_id: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ChannelViewed {
+    channel_id: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct UserAdded {
+    user_id: String,
+    #[serde(default)]
+    team_id: String,
+    #[serde(default)]
+    channel_id: String,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Status {
     pub status: String,
@@ -32,15 +93,121 @@ pub struct StatusDetails {
     is_oauth: Option<bool>,
 }
 
+/// The fields shared by `GenericPost` and `GenericPostEdited`, decoded from
+/// the JSON-encoded `post` string carried by `Posted`, `PostEdited` and
+/// `PostDeleted` alike.
+struct DecodedPost {
+    id: String,
+    user_id: String,
+    root_id: String,
+    parent_id: String,
+    message: String,
+    channel_id: String,
+}
+
+/// Decode `raw` (a `post` field) into its fields, falling back to something
+/// usable — rather than losing the message entirely — on a malformed or
+/// unexpected payload, using `fallback_user_id`/`fallback_channel_id` if the
+/// caller has something better than blank to offer (`Posted` does; `PostEdited`/
+/// `PostDeleted` don't carry that information outside of `post` itself).
+fn decode_post(raw: &str, fallback_user_id: &str, fallback_channel_id: &str) -> DecodedPost {
+    match serde_json::from_str::<Post>(raw) {
+        Ok(post) => DecodedPost {
+            id: post.id,
+            user_id: post.user_id,
+            root_id: post.root_id,
+            parent_id: post.parent_id,
+            message: post.message,
+            channel_id: post.channel_id,
+        },
+        Err(_) => DecodedPost {
+            id: "".to_string(),
+            user_id: fallback_user_id.to_string(),
+            root_id: "".to_string(),
+            parent_id: "".to_string(),
+            message: raw.to_string(),
+            channel_id: fallback_channel_id.to_string(),
+        },
+    }
+}
+
 impl Into<GenericPost> for Posted {
     fn into(self) -> GenericPost {
-        // FIXME: must still decode self.post
+        let decoded = decode_post(&self.post, &self.sender_name, &self.channel_name);
         GenericPost {
-            user_id: self.sender_name,
-            root_id: self.post.clone(),
-            parent_id: "".to_string(),
-            message: self.post.clone(),
-            channel_id: self.channel_name,
+            id: decoded.id,
+            user_id: decoded.user_id,
+            root_id: decoded.root_id,
+            parent_id: decoded.parent_id,
+            message: decoded.message,
+            channel_id: decoded.channel_id,
+            team_id: self.team_id,
+        }
+    }
+}
+
+impl Into<GenericPostEdited> for PostEdited {
+    fn into(self) -> GenericPostEdited {
+        let decoded = decode_post(&self.post, "", "");
+        GenericPostEdited {
+            id: decoded.id,
+            user_id: decoded.user_id,
+            root_id: decoded.root_id,
+            parent_id: decoded.parent_id,
+            message: decoded.message,
+            channel_id: decoded.channel_id,
+        }
+    }
+}
+
+impl Into<GenericPostEdited> for PostDeleted {
+    fn into(self) -> GenericPostEdited {
+        let decoded = decode_post(&self.post, "", "");
+        GenericPostEdited {
+            id: decoded.id,
+            user_id: decoded.user_id,
+            root_id: decoded.root_id,
+            parent_id: decoded.parent_id,
+            message: decoded.message,
+            channel_id: decoded.channel_id,
+        }
+    }
+}
+
+impl Into<GenericReaction> for Reaction {
+    fn into(self) -> GenericReaction {
+        GenericReaction {
+            user_id: self.user_id,
+            post_id: self.post_id,
+            emoji_name: self.emoji_name,
+        }
+    }
+}
+
+impl Into<GenericTyping> for Typing {
+    fn into(self) -> GenericTyping {
+        GenericTyping {
+            user_id: self.user_id,
+            channel_id: self.channel_id,
+            parent_id: self.parent_id,
+        }
+    }
+}
+
+impl Into<GenericChannelViewed> for ChannelViewed {
+    fn into(self) -> GenericChannelViewed {
+        GenericChannelViewed {
+            channel_id: self.channel_id,
+        }
+    }
+}
+
+impl Into<GenericUserAdded> for UserAdded {
+    fn into(self) -> GenericUserAdded {
+        GenericUserAdded {
+            user_id: self.user_id,
+            team_id: self.team_id,
+            channel_id: self.channel_id,
         }
     }
 }
@@ -79,20 +246,61 @@ impl Into<GenericStatus> for Status {
     }
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(untagged)]
+/// Events we know the shape of. Anything else falls back to [`EventData::Dynamic`]
+/// instead of failing to deserialize, following the approach flodgatt uses for the
+/// same Mattermost websocket protocol: don't let one unrecognised event name take
+/// down the whole connection.
 pub enum EventData {
     Posted(Posted),
+    PostEdited(PostEdited),
+    PostDeleted(PostDeleted),
+    ReactionAdded(Reaction),
+    ReactionRemoved(Reaction),
+    Typing(Typing),
+    ChannelViewed(ChannelViewed),
+    UserAdded(UserAdded),
+    Dynamic(String, serde_json::Value),
+}
+
+/// Envelope shape shared by every event, before we know which variant `data` holds.
+#[derive(Deserialize)]
+struct RawEvent {
+    event: String,
+    data: serde_json::Value,
 }
 
-#[derive(Serialize, Deserialize)]
 pub struct Event {
-    #[serde(rename(serialize = "event", deserialize = "event"))]
     type_: String,
     data: EventData,
 }
 
-#[derive(Serialize, Deserialize)]
+impl<'de> Deserialize<'de> for Event {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = RawEvent::deserialize(deserializer)?;
+        let data = match raw.event.as_str() {
+            "posted" => serde_json::from_value(raw.data).map(EventData::Posted),
+            "post_edited" => serde_json::from_value(raw.data).map(EventData::PostEdited),
+            "post_deleted" => serde_json::from_value(raw.data).map(EventData::PostDeleted),
+            "reaction_added" => serde_json::from_value(raw.data).map(EventData::ReactionAdded),
+            "reaction_removed" => serde_json::from_value(raw.data).map(EventData::ReactionRemoved),
+            "typing" => serde_json::from_value(raw.data).map(EventData::Typing),
+            "channel_viewed" => serde_json::from_value(raw.data).map(EventData::ChannelViewed),
+            "user_added" => serde_json::from_value(raw.data).map(EventData::UserAdded),
+            other => Ok(EventData::Dynamic(other.to_string(), raw.data)),
+        }
+        .map_err(D::Error::custom)?;
+
+        Ok(Event {
+            type_: raw.event,
+            data,
+        })
+    }
+}
+
+#[derive(Deserialize)]
 #[serde(untagged)]
 pub enum MetaEvent {
     Status(Status),
@@ -104,6 +312,14 @@ impl Into<GenericEvent> for Event {
     fn into(self) -> GenericEvent {
         match self.data {
             EventData::Posted(posted) => GenericEvent::Post(posted.into()),
+            EventData::PostEdited(edited) => GenericEvent::PostEdited(edited.into()),
+            EventData::PostDeleted(deleted) => GenericEvent::PostDeleted(deleted.into()),
+            EventData::ReactionAdded(reaction) => GenericEvent::ReactionAdded(reaction.into()),
+            EventData::ReactionRemoved(reaction) => GenericEvent::ReactionRemoved(reaction.into()),
+            EventData::Typing(typing) => GenericEvent::Typing(typing.into()),
+            EventData::ChannelViewed(viewed) => GenericEvent::ChannelViewed(viewed.into()),
+            EventData::UserAdded(added) => GenericEvent::UserAdded(added.into()),
+            EventData::Dynamic(name, value) => GenericEvent::Dynamic(name, value),
         }
     }
 }
@@ -146,6 +362,7 @@ mod tests {
                 assert_eq!(event.channel_type, "O");
                 assert_ne!(event.post, "");
             }
+            _ => panic!("wrong event data"),
         }
     }
 
@@ -156,6 +373,64 @@ mod tests {
         let _invalid: MetaEvent = serde_json::from_str(data).unwrap();
     }
 
+    #[test]
+    fn reaction_added_valid() {
+        let data = r#"{"event": "reaction_added", "data": {"user_id": "u1", "post_id": "p1", "emoji_name": "+1"}}"#;
+        let valid: MetaEvent = serde_json::from_str(data).unwrap();
+        let event = match valid {
+            MetaEvent::Event(event) => event,
+            _ => panic!("wrong type"),
+        };
+
+        match event.data {
+            EventData::ReactionAdded(reaction) => {
+                assert_eq!(reaction.user_id, "u1");
+                assert_eq!(reaction.post_id, "p1");
+                assert_eq!(reaction.emoji_name, "+1");
+            }
+            _ => panic!("wrong event data"),
+        }
+    }
+
+    #[test]
+    fn post_edited_valid() {
+        let data = r#"{"event": "post_edited", "data": {"post":"{\"id\":\"ghkm74cqzbnjxr5dx638k73xqa\",\"create_at\":1576937676623,\"update_at\":1576937676623,\"edit_at\":1576937680000,\"delete_at\":0,\"is_pinned\":false,\"user_id\":\"kh9859j8kir15dmxonsm8sxq1w\",\"channel_id\":\"amtak96j3br5iyokgunmf188jc\",\"root_id\":\"\",\"parent_id\":\"\",\"original_id\":\"\",\"message\":\"edited\",\"type\":\"\",\"props\":{},\"hashtags\":\"\",\"pending_post_id\":\"\",\"metadata\":{}}"}}"#;
+        let valid: MetaEvent = serde_json::from_str(data).unwrap();
+        let event = match valid {
+            MetaEvent::Event(event) => event,
+            _ => panic!("wrong type"),
+        };
+
+        match event.data {
+            EventData::PostEdited(edited) => {
+                let generic: GenericPostEdited = edited.into();
+                assert_eq!(generic.id, "ghkm74cqzbnjxr5dx638k73xqa");
+                assert_eq!(generic.user_id, "kh9859j8kir15dmxonsm8sxq1w");
+                assert_eq!(generic.channel_id, "amtak96j3br5iyokgunmf188jc");
+                assert_eq!(generic.message, "edited");
+            }
+            _ => panic!("wrong event data"),
+        }
+    }
+
+    #[test]
+    fn unknown_event_is_dynamic() {
+        let data = r#"{"event": "some_future_event", "data": {"foo": "bar"}}"#;
+        let valid: MetaEvent = serde_json::from_str(data).unwrap();
+        let event = match valid {
+            MetaEvent::Event(event) => event,
+            _ => panic!("wrong type"),
+        };
+
+        match event.data {
+            EventData::Dynamic(name, value) => {
+                assert_eq!(name, "some_future_event");
+                assert_eq!(value["foo"], "bar");
+            }
+            _ => panic!("wrong event data"),
+        }
+    }
+
     #[test]
     fn app_error() {
         let data = r#"{"status": "FAIL", "error": {"id": "api.web_socket_router.bad_seq.app_error", "message": "Invalid sequence for WebSocket message.", "detailed_error": "", "status_code": 400}}"#;
@@ -167,4 +442,4 @@ mod tests {
 
         assert_eq!("FAIL", status.status);
     }
-}
\ No newline at end of file
+}
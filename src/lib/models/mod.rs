@@ -1,13 +1,52 @@
 pub mod db;
 pub mod mattermost;
 
+pub use db::*;
+
 #[derive(Clone, Debug)]
 pub enum GenericEvent {
     Hello(GenericHello),
     Post(GenericPost),
     Status(GenericStatus),
+    /// The whole websocket message didn't parse as a known `Event`/`Status` shape.
     Unsupported(String),
     PostEdited(GenericPostEdited),
+    /// Same shape as an edit: channel/message/user/root/parent/id.
+    PostDeleted(GenericPostEdited),
+    ReactionAdded(GenericReaction),
+    ReactionRemoved(GenericReaction),
+    Typing(GenericTyping),
+    ChannelViewed(GenericChannelViewed),
+    UserAdded(GenericUserAdded),
+    /// An `Event` whose `event` name isn't one we have a typed struct for yet.
+    /// Carries the raw payload so a handler can still opt into it.
+    Dynamic(String, serde_json::Value),
+}
+
+#[derive(Clone, Debug)]
+pub struct GenericReaction {
+    pub user_id: String,
+    pub post_id: String,
+    pub emoji_name: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct GenericTyping {
+    pub user_id: String,
+    pub channel_id: String,
+    pub parent_id: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct GenericChannelViewed {
+    pub channel_id: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct GenericUserAdded {
+    pub user_id: String,
+    pub team_id: String,
+    pub channel_id: String,
 }
 
 #[derive(Clone, Debug)]
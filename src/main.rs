@@ -1,4 +1,3 @@
-use crossbeam::crossbeam_channel::unbounded;
 use crossbeam::sync::WaitGroup;
 use dotenv;
 use flobot::client::*;
@@ -19,19 +18,8 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
     let cfg = Conf::new().expect("cfg err");
     let db_url = cfg.db_url.as_str();
 
-    let (sender, receiver) = unbounded();
     let wg = WaitGroup::new();
 
-    {
-        let wg = wg.clone();
-        let cfg = cfg.clone();
-        thread::spawn(move || {
-            println!("launch client thread");
-            Mattermost::new(cfg).listen(sender);
-            drop(wg);
-        });
-    }
-
     println!("run db migrations");
     let conn = db::conn(db_url);
     db::run_migrations(&conn)?;
@@ -46,13 +34,96 @@ fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         Duration::from_secs(120),
     ));
     let edits = Box::new(handlers::edits::Edit::new(Rc::clone(&botdb)));
-    Instance::new(Mattermost::new(cfg.clone()))
+    let leek = Box::new(handlers::leek::Leek::new(Rc::new(Mattermost::new(cfg.clone()))));
+    let calc = Box::new(handlers::calc::Calc::new(Rc::new(Mattermost::new(cfg.clone()))));
+    let dice = Box::new(handlers::dice::Dice::new(Rc::new(Mattermost::new(cfg.clone()))));
+    let remind = Box::new(handlers::remind::Remind::new(
+        Rc::clone(&botdb),
+        Rc::new(Mattermost::new(cfg.clone())),
+    ));
+    let matchmaking = Box::new(handlers::matchmaking::Matchmaking::new(
+        Rc::clone(&botdb),
+        Rc::new(Mattermost::new(cfg.clone())),
+    ));
+
+    {
+        let db_url = db_url.to_string();
+        let scheduler_client = Mattermost::new(cfg.clone());
+        thread::spawn(move || {
+            let scheduler_db = dbs::Sqlite::new(db::conn(&db_url));
+            loop {
+                thread::sleep(Duration::from_secs(30));
+                let now = chrono::Utc::now().naive_utc();
+
+                if let Ok(due) = db::Reminders::due(&scheduler_db, now) {
+                    for reminder in due {
+                        let _ = scheduler_client.send(&reminder.channel_id, &format!("⏰ reminder: {}", reminder.message));
+                        let _ = db::Reminders::del(&scheduler_db, reminder.id);
+                    }
+                }
+
+                if let Ok(open) = db::Matches::open(&scheduler_db) {
+                    for m in open {
+                        // sync the roster from whoever has reacted so far,
+                        // excluding the bot's own reaction on the announcement.
+                        if let Ok(reactions) = scheduler_client.list_reactions(&m.message_id) {
+                            for user_id in reactions {
+                                if user_id == m.bot_user_id {
+                                    continue;
+                                }
+                                let _ = db::Matches::join(&scheduler_db, m.id, &user_id);
+                            }
+                        }
+
+                        let participants = db::Matches::participants(&scheduler_db, m.id).unwrap_or_default();
+                        let quorum_reached = participants.len() >= handlers::matchmaking::QUORUM;
+
+                        if quorum_reached || m.start_at <= now {
+                            let roster = if participants.is_empty() {
+                                "nobody joined \u{1F622}".to_string()
+                            } else {
+                                participants.iter().map(|u| format!("@{}", u)).collect::<Vec<_>>().join(", ")
+                            };
+                            let _ = scheduler_client.send(
+                                &m.channel_id,
+                                &format!("\u{1F3C1} {} is starting now! {}", m.activity, roster),
+                            );
+                            let _ = db::Matches::del(&scheduler_db, m.id);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    let mut instance = Instance::new(
+        flobot::client::RateLimited::new(Mattermost::new(cfg.clone())),
+        cfg.event_buffer,
+    );
+
+    {
+        let wg = wg.clone();
+        let cfg = cfg.clone();
+        let sender = instance.sender();
+        thread::spawn(move || {
+            println!("launch client thread");
+            Mattermost::new(cfg).listen(sender);
+            drop(wg);
+        });
+    }
+
+    instance
         //.add_middleware(Box::new(middleware::Debug::new("debug")))
         .add_middleware(ignore_self)
         //.add_post_handler(Box::new(trigger))
         .add_post_handler(trigger)
         .add_post_handler(edits)
-        .run(receiver.clone())?;
+        .add_post_handler(leek)
+        .add_post_handler(calc)
+        .add_post_handler(dice)
+        .add_post_handler(remind)
+        .add_post_handler(matchmaking)
+        .run(&cfg.state_path)?;
 
     drop(botdb);
     println!("waiting for listener to stop");